@@ -1,28 +1,80 @@
-use super::id_generation::TicketId;
+mod audit;
+mod contracts;
+mod persistence;
+mod transaction;
+
+pub use audit::AuditEntry;
+pub use persistence::PersistentTicketStore;
+pub use transaction::Transaction;
+
 use super::recap::Status;
+use audit::ChangeRecord;
 use chrono::{DateTime, Utc};
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
 use std::error::Error;
+use std::io;
+
+/// A generational index into `TicketStore`'s slot arena, inspired by the design used by
+/// `generational-arena`: `index` locates the slot, `generation` disambiguates it from whatever
+/// ticket might have previously lived in - or will later be placed into - that same slot.
+///
+/// This supersedes the bare, strictly-increasing counter the earlier koans used: that scheme
+/// never let us reclaim a deleted ticket's slot, since any later id compared equal only by its
+/// counter value, with no way to tell "the original ticket" apart from "whatever moved in
+/// after it". Comparing both `index` and `generation` means a `TicketId` that refers to a
+/// deleted ticket can never accidentally resolve to a new ticket that reuses its slot.
+///
+/// There is no separate counter to persist either: `allocate_id` derives every id from
+/// `slots`/`free_list`, both of which are fully reconstructed when a `PersistentTicketStore`
+/// replays its log, so a reopened store never hands out an id it had already handed out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct TicketId {
+    index: u32,
+    generation: u32,
+}
+
+/// One slot in `TicketStore`'s arena. `generation` is bumped every time the slot's ticket is
+/// deleted, and outlives the ticket itself - that's what lets us tell a reused slot apart from
+/// the one a stale `TicketId` was pointing at.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Slot {
+    generation: u32,
+    ticket: Option<Ticket>,
+}
 
 /// There are only two pieces missing: deleting a ticket and updating a ticket
 /// in our `TicketStore`.
 /// The update functionality will give us the possibility to change the `status` of
 /// a ticket, the holy grail of our JIRA clone.
+///
+/// The WAL/snapshot persistence layer, the buffered transaction handle, the tamper-evident
+/// audit trail and the `contracts`-gated invariant checks each live in their own module
+/// (`persistence`, `transaction`, `audit`, `contracts`) alongside this one - every one of them
+/// is a substantial, independent concern in its own right, and grouping them here would make
+/// this module's scope a lot bigger than "delete and update".
 struct TicketStore {
-    data: HashMap<TicketId, Ticket>,
-    current_id: TicketId,
+    slots: Vec<Slot>,
+    /// Indices of slots whose ticket has been deleted and can be handed out again.
+    free_list: Vec<u32>,
+    /// A hash-linked, append-only trail of every mutation this store has ever applied.
+    /// See `record_audit_entry`, `audit_root` and `verify_chain` in the `audit` module.
+    audit_log: Vec<AuditEntry>,
 }
 
 impl TicketStore {
     pub fn new() -> TicketStore {
         TicketStore {
-            data: HashMap::new(),
-            current_id: 0,
+            slots: Vec::new(),
+            free_list: Vec::new(),
+            audit_log: Vec::new(),
         }
     }
 
     pub fn save(&mut self, draft: TicketDraft) -> TicketId {
-        let id = self.generate_id();
+        #[cfg(feature = "contracts")]
+        let tickets_before = self.list().len();
+
+        let id = self.allocate_id();
         let timestamp = Utc::now();
         let ticket = Ticket {
             id,
@@ -34,16 +86,36 @@ impl TicketStore {
             // It starts in sync with `created_at`, it gets updated when a ticket is updated.
             updated_at: timestamp,
         };
-        self.data.insert(id, ticket);
+        self.slots[id.index as usize].ticket = Some(ticket.clone());
+        self.record_audit_entry(ChangeRecord::Save(ticket));
+
+        #[cfg(feature = "contracts")]
+        {
+            assert!(
+                self.get(&id).is_some(),
+                "postcondition violated: `save` must return an id present in the store"
+            );
+            assert_eq!(
+                tickets_before + 1,
+                self.list().len(),
+                "postcondition violated: `save` must grow the store by exactly one ticket"
+            );
+            self.check_invariants();
+        }
+
         id
     }
 
     pub fn get(&self, id: &TicketId) -> Option<&Ticket> {
-        self.data.get(id)
+        let slot = self.slots.get(id.index as usize)?;
+        if slot.generation != id.generation {
+            return None;
+        }
+        slot.ticket.as_ref()
     }
 
     pub fn list(&self) -> Vec<&Ticket> {
-        self.data.values().collect()
+        self.slots.iter().filter_map(|slot| slot.ticket.as_ref()).collect()
     }
 
     /// We take in an `id` and a `patch` struct: this allows us to constrain which of the
@@ -56,9 +128,18 @@ impl TicketStore {
     /// it as they wanted, we wouldn't have been able to uphold the same guarantees.
     ///
     /// If the `id` passed in matches a ticket in the store, we return the edited ticket.
-    /// If it doesn't, we return `None`.
-    pub fn update(&mut self, id: &TicketId, patch: TicketPatch) -> Option<&Ticket> {
-        self.data.get_mut(id).map(|ticket| {
+    /// If it doesn't, we return `StoreError::TicketNotFound`.
+    pub fn update(&mut self, id: &TicketId, patch: TicketPatch) -> Result<&Ticket, StoreError> {
+        #[cfg(feature = "contracts")]
+        let ticket_before_patch = self.get(id).cloned();
+
+        let not_found = StoreError::TicketNotFound(*id);
+        let slot = self.slots.get_mut(id.index as usize).ok_or(not_found)?;
+        if slot.generation != id.generation {
+            return Err(StoreError::TicketNotFound(*id));
+        }
+        let ticket = {
+            let ticket = slot.ticket.as_mut().ok_or(StoreError::TicketNotFound(*id))?;
             if let Some(title) = patch.title {
                 ticket.title = title
             }
@@ -71,26 +152,109 @@ impl TicketStore {
                 ticket.status = status;
             }
             ticket.updated_at = Utc::now();
-            &(*ticket)
-        })
+            ticket.clone()
+        };
+        self.record_audit_entry(ChangeRecord::Update(ticket));
+        let updated = self.get(id).expect("we just updated this ticket");
+
+        #[cfg(feature = "contracts")]
+        {
+            let before = ticket_before_patch.expect("we already confirmed this id exists above");
+            assert_eq!(
+                before.id, updated.id,
+                "postcondition violated: `update` must not change a ticket's id"
+            );
+            assert_eq!(
+                before.created_at, updated.created_at,
+                "postcondition violated: `update` must not change a ticket's created_at"
+            );
+            assert!(
+                updated.updated_at >= updated.created_at,
+                "postcondition violated: `updated_at` must never precede `created_at`"
+            );
+            self.check_invariants();
+        }
+
+        Ok(updated)
     }
 
     /// If the `id` passed in matches a ticket in the store, we return the deleted ticket
     /// with some additional metadata.
-    /// If it doesn't, we return `None`.
-    pub fn delete(&mut self, id: &TicketId) -> Option<DeletedTicket> {
-        self.data.remove(id).map(|ticket| DeletedTicket {
+    /// If it doesn't, we return `StoreError::TicketNotFound`.
+    pub fn delete(&mut self, id: &TicketId) -> Result<DeletedTicket, StoreError> {
+        #[cfg(feature = "contracts")]
+        let tickets_before = self.list().len();
+
+        let not_found = StoreError::TicketNotFound(*id);
+        let slot = self.slots.get_mut(id.index as usize).ok_or(not_found)?;
+        if slot.generation != id.generation {
+            return Err(StoreError::TicketNotFound(*id));
+        }
+        let ticket = slot.ticket.take().ok_or(StoreError::TicketNotFound(*id))?;
+        slot.generation += 1;
+        self.free_list.push(id.index);
+        self.record_audit_entry(ChangeRecord::Delete(*id));
+
+        #[cfg(feature = "contracts")]
+        {
+            assert!(
+                self.get(id).is_none(),
+                "postcondition violated: `delete` must leave the id absent from the store"
+            );
+            assert_eq!(
+                tickets_before - 1,
+                self.list().len(),
+                "postcondition violated: `delete` must shrink the store by exactly one ticket"
+            );
+            self.check_invariants();
+        }
+
+        Ok(DeletedTicket {
             ticket,
             deleted_at: Utc::now(),
         })
     }
 
-    fn generate_id(&mut self) -> TicketId {
-        self.current_id += 1;
-        self.current_id
+    /// Hands out a fresh `TicketId`, reusing a freed slot's index (and its already-bumped
+    /// generation) whenever one is available, instead of growing the arena forever.
+    fn allocate_id(&mut self) -> TicketId {
+        if let Some(index) = self.free_list.pop() {
+            TicketId {
+                index,
+                generation: self.slots[index as usize].generation,
+            }
+        } else {
+            let index = self.slots.len() as u32;
+            self.slots.push(Slot {
+                generation: 0,
+                ticket: None,
+            });
+            TicketId { index, generation: 0 }
+        }
+    }
+
+    /// Opens a transaction: a handle that lets a caller stage several `save`/`update`/`delete`
+    /// operations and apply them to the store in one shot, or not at all.
+    ///
+    /// Nothing the transaction does is visible to the store until `Transaction::commit` is
+    /// called. Dropping the transaction without committing simply discards whatever was
+    /// staged, which gives us rollback for free.
+    pub fn begin(&mut self) -> Transaction<'_> {
+        Transaction::new(self)
     }
 }
 
+/// `TicketPatch` constrains the fields that we consider editable.
+///
+/// If a field is set the `Some`, its value will be updated to the specified value.
+/// If a field is set to `None`, the field remains unchanged.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TicketPatch {
+    pub title: Option<TicketTitle>,
+    pub description: Option<TicketDescription>,
+    pub status: Option<Status>,
+}
+
 /// We don't want to relax our constraints on what is an acceptable title or an acceptable
 /// description for a ticket.
 /// This means that we need to validate the `title` and the `description` in our `TicketPatch`
@@ -98,49 +262,36 @@ impl TicketStore {
 ///
 /// To keep it DRY, we introduce two new types whose constructors guarantee the invariants
 /// we care about.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TicketTitle(String);
 
 impl TicketTitle {
     pub fn new(title: String) -> Result<Self, ValidationError> {
         if title.is_empty() {
-            return Err(ValidationError("Title cannot be empty!".to_string()));
+            return Err(ValidationError::EmptyTitle);
         }
         if title.len() > 50 {
-            return Err(ValidationError(
-                "A title cannot be longer than 50 characters!".to_string(),
-            ));
+            return Err(ValidationError::TitleTooLong { len: title.len() });
         }
         Ok(Self(title))
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TicketDescription(String);
 
 impl TicketDescription {
     pub fn new(description: String) -> Result<Self, ValidationError> {
         if description.len() > 3000 {
-            Err(ValidationError(
-                "A description cannot be longer than 3000 characters!".to_string(),
-            ))
+            Err(ValidationError::DescriptionTooLong {
+                len: description.len(),
+            })
         } else {
             Ok(Self(description))
         }
     }
 }
 
-/// `TicketPatch` constrains the fields that we consider editable.
-///
-/// If a field is set the `Some`, its value will be updated to the specified value.
-/// If a field is set to `None`, the field remains unchanged.
-#[derive(Debug, Clone, PartialEq)]
-pub struct TicketPatch {
-    pub title: Option<TicketTitle>,
-    pub description: Option<TicketDescription>,
-    pub status: Option<Status>,
-}
-
 /// With validation baked in our types, we don't have to worry anymore about the visibility
 /// of those fields.
 /// Our `TicketPatch` and our `TicketDraft` don't have an identity, an id, like a `Ticket`
@@ -174,18 +325,82 @@ impl DeletedTicket {
     }
 }
 
+/// Why a `TicketTitle` or a `TicketDescription` failed to be constructed.
+///
+/// Carrying a structured field per cause - rather than a pre-formatted message - lets callers
+/// match on the reason a ticket draft was rejected instead of having to parse a string.
 #[derive(PartialEq, Debug, Clone)]
-pub struct ValidationError(String);
+pub enum ValidationError {
+    EmptyTitle,
+    TitleTooLong { len: usize },
+    DescriptionTooLong { len: usize },
+}
 
 impl Error for ValidationError {}
 
 impl std::fmt::Display for ValidationError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "{}", self.0)
+        match self {
+            ValidationError::EmptyTitle => write!(f, "Title cannot be empty!"),
+            ValidationError::TitleTooLong { len } => write!(
+                f,
+                "A title cannot be longer than 50 characters! (got {len})"
+            ),
+            ValidationError::DescriptionTooLong { len } => write!(
+                f,
+                "A description cannot be longer than 3000 characters! (got {len})"
+            ),
+        }
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+/// The error type every fallible `TicketStore` operation returns.
+///
+/// Implementing `source()` lets the underlying cause - a validation failure, an I/O error once
+/// persistence is involved - cross this abstraction boundary without being flattened into a
+/// string, so callers (and `anyhow`/`eyre`-style reporters) can still walk the full chain.
+#[derive(Debug)]
+pub enum StoreError {
+    Validation(ValidationError),
+    TicketNotFound(TicketId),
+    Io(io::Error),
+}
+
+impl std::fmt::Display for StoreError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            StoreError::Validation(_) => write!(f, "the ticket draft failed validation"),
+            StoreError::TicketNotFound(id) => {
+                write!(f, "no ticket found for id {id:?}")
+            }
+            StoreError::Io(_) => write!(f, "a persistence operation failed"),
+        }
+    }
+}
+
+impl Error for StoreError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            StoreError::Validation(error) => Some(error),
+            StoreError::TicketNotFound(_) => None,
+            StoreError::Io(error) => Some(error),
+        }
+    }
+}
+
+impl From<ValidationError> for StoreError {
+    fn from(error: ValidationError) -> Self {
+        StoreError::Validation(error)
+    }
+}
+
+impl From<io::Error> for StoreError {
+    fn from(error: io::Error) -> Self {
+        StoreError::Io(error)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Ticket {
     id: TicketId,
     title: TicketTitle,
@@ -219,7 +434,8 @@ impl Ticket {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use fake::{Fake, Faker};
+    use fake::Fake;
+    use super::persistence::SNAPSHOT_INTERVAL;
     use std::time::Duration;
 
     #[test]
@@ -241,12 +457,15 @@ mod tests {
     }
 
     #[test]
-    fn trying_to_update_a_missing_ticket_returns_none() {
+    fn trying_to_update_a_missing_ticket_returns_an_error() {
         let mut store = TicketStore::new();
-        let ticket_id = Faker.fake();
+        let ticket_id = missing_ticket_id();
         let patch = generate_ticket_patch(Status::Done);
 
-        assert_eq!(store.update(&ticket_id, patch), None);
+        assert_eq!(
+            StoreError::TicketNotFound(ticket_id).to_string(),
+            store.update(&ticket_id, patch).unwrap_err().to_string()
+        );
     }
 
     #[test]
@@ -281,11 +500,14 @@ mod tests {
     }
 
     #[test]
-    fn deleting_a_missing_ticket_returns_none() {
+    fn deleting_a_missing_ticket_returns_an_error() {
         let mut store = TicketStore::new();
-        let ticket_id = Faker.fake();
+        let ticket_id = missing_ticket_id();
 
-        assert_eq!(store.delete(&ticket_id), None);
+        assert!(matches!(
+            store.delete(&ticket_id),
+            Err(StoreError::TicketNotFound(id)) if id == ticket_id
+        ));
     }
 
     #[test]
@@ -356,33 +578,67 @@ mod tests {
     #[test]
     fn a_missing_ticket() {
         let ticket_store = TicketStore::new();
-        let ticket_id = Faker.fake();
+        let ticket_id = missing_ticket_id();
 
         assert_eq!(ticket_store.get(&ticket_id), None);
     }
 
     #[test]
-    fn id_generation_is_monotonic() {
+    fn fresh_ids_get_sequential_slot_indices() {
         let n_tickets = 100;
         let mut store = TicketStore::new();
 
-        for expected_id in 1..n_tickets {
+        for expected_index in 0..n_tickets {
             let draft = generate_ticket_draft();
             let ticket_id = store.save(draft);
-            assert_eq!(expected_id, ticket_id);
+            assert_eq!(expected_index, ticket_id.index);
+            assert_eq!(0, ticket_id.generation);
         }
     }
 
     #[test]
-    fn ids_are_not_reused() {
-        let n_tickets = 100;
+    fn a_freed_slot_is_reused_with_a_bumped_generation() {
         let mut store = TicketStore::new();
+        let first_id = store.save(generate_ticket_draft());
+        store.delete(&first_id).unwrap();
 
-        for expected_id in 1..n_tickets {
-            let draft = generate_ticket_draft();
-            let ticket_id = store.save(draft);
-            assert_eq!(expected_id, ticket_id);
-            assert!(store.delete(&ticket_id).is_some());
+        let second_id = store.save(generate_ticket_draft());
+
+        assert_eq!(first_id.index, second_id.index);
+        assert_ne!(first_id, second_id);
+        assert!(store.get(&first_id).is_none());
+        assert!(store.get(&second_id).is_some());
+    }
+
+    #[cfg(feature = "contracts")]
+    #[test]
+    fn check_invariants_accepts_a_freshly_mutated_store() {
+        let mut store = TicketStore::new();
+        let ticket_id = store.save(generate_ticket_draft());
+        store
+            .update(&ticket_id, generate_ticket_patch(Status::Done))
+            .unwrap();
+        store.delete(&ticket_id).unwrap();
+        store.save(generate_ticket_draft());
+
+        store.check_invariants();
+    }
+
+    #[cfg(feature = "contracts")]
+    #[test]
+    #[should_panic(expected = "invariant violated")]
+    fn check_invariants_catches_a_slot_generation_mismatch() {
+        let mut store = TicketStore::new();
+        store.save(generate_ticket_draft());
+        store.slots[0].generation = 7;
+
+        store.check_invariants();
+    }
+
+    fn missing_ticket_id() -> TicketId {
+        TicketId {
+            index: 9_999,
+            generation: 9_999,
         }
     }
 
@@ -393,6 +649,238 @@ mod tests {
         TicketDraft { title, description }
     }
 
+    #[test]
+    fn the_audit_root_changes_with_every_mutation() {
+        let mut store = TicketStore::new();
+        let empty_root = store.audit_root();
+
+        let ticket_id = store.save(generate_ticket_draft());
+        let root_after_save = store.audit_root();
+        assert_ne!(empty_root, root_after_save);
+
+        store
+            .update(&ticket_id, generate_ticket_patch(Status::Done))
+            .unwrap();
+        let root_after_update = store.audit_root();
+        assert_ne!(root_after_save, root_after_update);
+
+        store.delete(&ticket_id).unwrap();
+        assert_ne!(root_after_update, store.audit_root());
+    }
+
+    #[test]
+    fn a_freshly_recorded_chain_verifies_successfully() {
+        let mut store = TicketStore::new();
+        for _ in 0..5 {
+            store.save(generate_ticket_draft());
+        }
+
+        assert_eq!(Ok(()), store.verify_chain());
+    }
+
+    #[test]
+    fn tampering_with_a_past_entry_is_detected() {
+        let mut store = TicketStore::new();
+        for _ in 0..3 {
+            store.save(generate_ticket_draft());
+        }
+
+        store.audit_log[0].hash = [0xAB; 32];
+
+        assert_eq!(Err(0), store.verify_chain());
+    }
+
+    #[test]
+    fn a_committed_transaction_is_visible_through_the_store() {
+        let mut store = TicketStore::new();
+        let draft = generate_ticket_draft();
+
+        let mut tx = store.begin();
+        let ticket_id = tx.save(draft.clone());
+        tx.commit();
+
+        let ticket = store.get(&ticket_id).unwrap();
+        assert_eq!(&draft.title, ticket.title());
+    }
+
+    #[test]
+    fn a_dropped_transaction_is_rolled_back() {
+        let mut store = TicketStore::new();
+        let draft = generate_ticket_draft();
+
+        {
+            let mut tx = store.begin();
+            tx.save(draft);
+            // `tx` is dropped here without being committed.
+        }
+
+        assert!(store.list().is_empty());
+    }
+
+    #[test]
+    fn a_transaction_sees_its_own_staged_changes() {
+        let mut store = TicketStore::new();
+        let draft = generate_ticket_draft();
+
+        let mut tx = store.begin();
+        let ticket_id = tx.save(draft.clone());
+
+        assert_eq!(&draft.title, tx.get(&ticket_id).unwrap().title());
+        assert_eq!(1, tx.list().len());
+    }
+
+    #[test]
+    fn a_transaction_with_a_missing_id_in_its_batch_can_be_rolled_back_as_a_whole() {
+        let mut store = TicketStore::new();
+        let ticket_id = store.save(generate_ticket_draft());
+
+        let mut tx = store.begin();
+        assert!(tx.delete(&ticket_id).is_ok());
+        let missing_id = missing_ticket_id();
+        assert!(tx.delete(&missing_id).is_err());
+        // We decide not to commit, since one of the deletes targeted a missing ticket.
+        drop(tx);
+
+        assert!(store.get(&ticket_id).is_some());
+    }
+
+    #[test]
+    fn committing_a_transaction_extends_the_audit_chain() {
+        let mut store = TicketStore::new();
+        let root_before_commit = store.audit_root();
+
+        let mut tx = store.begin();
+        let ticket_id = tx.save(generate_ticket_draft());
+        tx.commit();
+
+        assert_ne!(root_before_commit, store.audit_root());
+        assert_eq!(Ok(()), store.verify_chain());
+        assert!(matches!(
+            store.audit_log.last().map(|entry| &entry.op),
+            Some(ChangeRecord::Save(ticket)) if ticket.id == ticket_id
+        ));
+    }
+
+    #[test]
+    fn committing_a_transaction_update_records_an_update_not_a_save() {
+        let mut store = TicketStore::new();
+        let ticket_id = store.save(generate_ticket_draft());
+
+        let mut tx = store.begin();
+        tx.update(&ticket_id, generate_ticket_patch(Status::Done)).unwrap();
+        tx.commit();
+
+        assert!(matches!(
+            store.audit_log.last().map(|entry| &entry.op),
+            Some(ChangeRecord::Update(ticket)) if ticket.id == ticket_id
+        ));
+    }
+
+    #[test]
+    fn a_persistent_store_survives_being_reopened() {
+        let dir = tempfile::tempdir().unwrap();
+        let draft = generate_ticket_draft();
+
+        let ticket_id = {
+            let mut store = PersistentTicketStore::open(dir.path()).unwrap();
+            store.save(draft.clone()).unwrap()
+        };
+
+        let store = PersistentTicketStore::open(dir.path()).unwrap();
+        let ticket = store.get(&ticket_id).unwrap();
+        assert_eq!(&draft.title, ticket.title());
+        assert_eq!(&draft.description, ticket.description());
+    }
+
+    #[test]
+    fn a_persistent_store_replays_deletes_on_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let draft = generate_ticket_draft();
+
+        {
+            let mut store = PersistentTicketStore::open(dir.path()).unwrap();
+            let ticket_id = store.save(draft).unwrap();
+            store.delete(&ticket_id).unwrap();
+        }
+
+        let store = PersistentTicketStore::open(dir.path()).unwrap();
+        assert!(store.list().is_empty());
+    }
+
+    #[test]
+    fn a_persistent_store_does_not_reuse_ids_after_a_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let first_id = {
+            let mut store = PersistentTicketStore::open(dir.path()).unwrap();
+            store.save(generate_ticket_draft()).unwrap()
+        };
+
+        let mut store = PersistentTicketStore::open(dir.path()).unwrap();
+        let second_id = store.save(generate_ticket_draft()).unwrap();
+
+        assert_ne!(first_id, second_id);
+    }
+
+    #[test]
+    fn a_persistent_store_does_not_double_free_a_slot_reused_before_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let (first_id, second_id) = {
+            let mut store = PersistentTicketStore::open(dir.path()).unwrap();
+            let first_id = store.save(generate_ticket_draft()).unwrap();
+            store.delete(&first_id).unwrap();
+            // Reuses the slot `first_id` just freed, at the bumped generation.
+            let second_id = store.save(generate_ticket_draft()).unwrap();
+            (first_id, second_id)
+        };
+
+        let mut store = PersistentTicketStore::open(dir.path()).unwrap();
+        // The reused slot must come off the free list during replay - otherwise this `save`
+        // would hand out `first_id`'s bits again and clobber the ticket `second_id` points at.
+        let third_id = store.save(generate_ticket_draft()).unwrap();
+
+        assert!(store.get(&second_id).is_some());
+        assert_ne!(second_id, third_id);
+        assert_ne!(first_id, third_id);
+    }
+
+    #[test]
+    fn a_persistent_store_audit_trail_survives_reopen_before_a_snapshot() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let root_before_reopen = {
+            let mut store = PersistentTicketStore::open(dir.path()).unwrap();
+            store.save(generate_ticket_draft()).unwrap();
+            store.audit_root()
+        };
+
+        // Only one change has been made, far below `SNAPSHOT_INTERVAL`, so this reopen replays
+        // the audit entry from the WAL rather than loading it from a snapshot.
+        let store = PersistentTicketStore::open(dir.path()).unwrap();
+        assert_eq!(root_before_reopen, store.audit_root());
+        assert_eq!(Ok(()), store.verify_chain());
+    }
+
+    #[test]
+    fn a_persistent_store_audit_trail_survives_reopen_after_a_snapshot() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let root_before_reopen = {
+            let mut store = PersistentTicketStore::open(dir.path()).unwrap();
+            for _ in 0..SNAPSHOT_INTERVAL {
+                store.save(generate_ticket_draft()).unwrap();
+            }
+            store.audit_root()
+        };
+
+        // `SNAPSHOT_INTERVAL` changes have piled up, so this reopen loads `audit_log` straight
+        // from the snapshot instead of replaying it from the (now-truncated) WAL.
+        let store = PersistentTicketStore::open(dir.path()).unwrap();
+        assert_eq!(root_before_reopen, store.audit_root());
+        assert_eq!(Ok(()), store.verify_chain());
+    }
+
     fn generate_ticket_patch(status: Status) -> TicketPatch {
         let patch = generate_ticket_draft();
 
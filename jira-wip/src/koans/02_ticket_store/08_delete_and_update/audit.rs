@@ -0,0 +1,77 @@
+use super::{Ticket, TicketId, TicketStore};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// One entry in the write-ahead log: the operation that was performed, together with
+/// whatever payload is needed to replay it. Doubles as the payload recorded in the audit
+/// trail below, since replaying a WAL record and auditing a mutation are the same event.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) enum ChangeRecord {
+    Save(Ticket),
+    Update(Ticket),
+    Delete(TicketId),
+}
+
+/// One entry in the store's tamper-evident audit trail.
+///
+/// `hash` commits to both `op` and the hash of the entry before it (`sha256(prev_hash ||
+/// op_bytes)`), so changing any past entry - or reordering, dropping, duplicating one - changes
+/// every hash computed after it. `verify_chain` relies on exactly that property.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub(super) op: ChangeRecord,
+    pub(super) timestamp: DateTime<Utc>,
+    pub(super) hash: [u8; 32],
+}
+
+/// The hash that seeds the chain: the "previous hash" of the very first audit entry.
+const GENESIS_HASH: [u8; 32] = [0; 32];
+
+impl TicketStore {
+    /// Appends a new entry to the audit trail, hashing `op` together with the previous
+    /// entry's hash so the chain commits to its full history.
+    pub(super) fn record_audit_entry(&mut self, op: ChangeRecord) {
+        let prev_hash = self.audit_log.last().map_or(GENESIS_HASH, |entry| entry.hash);
+        let op_bytes = serde_json::to_vec(&op).expect("a `ChangeRecord` always serializes");
+
+        let mut hasher = Sha256::new();
+        hasher.update(prev_hash);
+        hasher.update(&op_bytes);
+        let hash = hasher.finalize().into();
+
+        self.audit_log.push(AuditEntry {
+            op,
+            timestamp: Utc::now(),
+            hash,
+        });
+    }
+
+    /// The hash of the latest audit entry, committing to the whole history of mutations
+    /// this store has applied so far.
+    pub fn audit_root(&self) -> [u8; 32] {
+        self.audit_log.last().map_or(GENESIS_HASH, |entry| entry.hash)
+    }
+
+    /// Recomputes the audit chain from scratch and checks it against the stored hashes.
+    ///
+    /// Returns the index of the first entry whose hash no longer matches - which can only
+    /// happen if that entry, or one before it, was tampered with after the fact.
+    pub fn verify_chain(&self) -> Result<(), usize> {
+        let mut prev_hash = GENESIS_HASH;
+        for (index, entry) in self.audit_log.iter().enumerate() {
+            let op_bytes =
+                serde_json::to_vec(&entry.op).expect("a `ChangeRecord` always serializes");
+            let mut hasher = Sha256::new();
+            hasher.update(prev_hash);
+            hasher.update(&op_bytes);
+            let expected_hash: [u8; 32] = hasher.finalize().into();
+
+            if expected_hash != entry.hash {
+                return Err(index);
+            }
+            prev_hash = entry.hash;
+        }
+        Ok(())
+    }
+}
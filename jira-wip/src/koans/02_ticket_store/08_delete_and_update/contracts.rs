@@ -0,0 +1,35 @@
+use super::TicketStore;
+
+impl TicketStore {
+    /// Store-wide invariants that must hold before and after every mutating operation.
+    ///
+    /// Only compiled in when the `contracts` feature is enabled, in the spirit of the
+    /// `contracts` crate's `pre`/`post`/`invariant` attributes: these checks are valuable while
+    /// developing and testing, but expensive enough (an `O(slots)` scan) that we don't want to
+    /// pay for them in a release build. Panics rather than returning a `Result`, since a
+    /// violation here points at a bug in `TicketStore` itself, not at something a caller did
+    /// wrong.
+    #[cfg(feature = "contracts")]
+    pub(super) fn check_invariants(&self) {
+        for (index, slot) in self.slots.iter().enumerate() {
+            if let Some(ticket) = &slot.ticket {
+                assert_eq!(
+                    index as u32, ticket.id.index,
+                    "invariant violated: the ticket in slot {index} thinks its id's index is {}",
+                    ticket.id.index
+                );
+                assert_eq!(
+                    slot.generation, ticket.id.generation,
+                    "invariant violated: slot {index}'s generation ({}) doesn't match its ticket's id generation ({})",
+                    slot.generation, ticket.id.generation
+                );
+            }
+        }
+        for &index in &self.free_list {
+            assert!(
+                self.slots[index as usize].ticket.is_none(),
+                "invariant violated: slot {index} is on the free list but still holds a ticket"
+            );
+        }
+    }
+}
@@ -0,0 +1,295 @@
+use super::audit::ChangeRecord;
+use super::{
+    AuditEntry, DeletedTicket, Slot, Status, StoreError, Ticket, TicketDraft, TicketId,
+    TicketPatch, TicketStore,
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// `TicketStore` keeps everything in memory: the moment the process exits, every ticket is
+/// gone. `PersistentTicketStore` wraps it with a small write-ahead log so that it survives
+/// restarts, without giving up the simplicity of an in-memory arena for reads.
+///
+/// Every mutation is first appended to `log_path` as a serialized `ChangeRecord` and only
+/// then applied to the in-memory `store`. Once `SNAPSHOT_INTERVAL` changes have piled up, we
+/// write out a full `Snapshot` of `store` to `snapshot_path` and truncate the log, so it
+/// never grows without bound. `open` reverses the process: load the latest snapshot (or start
+/// from an empty store if there isn't one yet), then replay whatever records are left in the
+/// log on top of it to reconstruct the exact state we were in before we went away, slots and
+/// free list included, so freshly generated ids never collide with ids handed out before the
+/// crash.
+///
+/// The audit trail from [`TicketStore::record_audit_entry`] is reconstructed the same way: the
+/// latest `Snapshot` carries `audit_log` up to the point it was taken, and `apply` extends it
+/// with one entry per WAL record replayed on top - so the hash chain picks up exactly where it
+/// left off, regardless of whether the state it's describing came from the snapshot or the
+/// log. `audit_root`/`verify_chain` on a `PersistentTicketStore` therefore cover the store's
+/// entire history, not just what happened since the last `open`.
+pub struct PersistentTicketStore {
+    store: TicketStore,
+    snapshot_path: PathBuf,
+    log_path: PathBuf,
+    log_writer: BufWriter<File>,
+    changes_since_snapshot: usize,
+}
+
+/// How many change records we let pile up in the log before folding them into a fresh
+/// snapshot and starting the log over.
+pub(super) const SNAPSHOT_INTERVAL: usize = 100;
+
+/// A full point-in-time copy of a `TicketStore`'s state, serialized to `snapshot_path`.
+///
+/// `audit_log` is included alongside `slots`/`free_list` so the tamper-evident trail survives a
+/// snapshot-and-truncate cycle exactly like the rest of the store's state does.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Snapshot {
+    slots: Vec<Slot>,
+    free_list: Vec<u32>,
+    audit_log: Vec<AuditEntry>,
+}
+
+impl PersistentTicketStore {
+    /// Opens the store rooted at `dir`, creating it if it doesn't exist yet.
+    ///
+    /// The directory will end up holding two files: `snapshot` (the latest full snapshot)
+    /// and `log` (the change records appended since that snapshot).
+    pub fn open(dir: &Path) -> Result<Self, StoreError> {
+        std::fs::create_dir_all(dir)?;
+        let snapshot_path = dir.join("snapshot");
+        let log_path = dir.join("log");
+
+        let mut store = if snapshot_path.exists() {
+            let file = File::open(&snapshot_path)?;
+            let snapshot: Snapshot =
+                serde_json::from_reader(BufReader::new(file)).map_err(to_io_error)?;
+            TicketStore {
+                slots: snapshot.slots,
+                free_list: snapshot.free_list,
+                audit_log: snapshot.audit_log,
+            }
+        } else {
+            TicketStore::new()
+        };
+
+        let mut changes_since_snapshot = 0;
+        if log_path.exists() {
+            let file = File::open(&log_path)?;
+            for line in BufReader::new(file).lines() {
+                let record: ChangeRecord = serde_json::from_str(&line?).map_err(to_io_error)?;
+                apply(&mut store, record);
+                changes_since_snapshot += 1;
+            }
+        }
+
+        let log_writer = BufWriter::new(
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&log_path)?,
+        );
+
+        let mut persistent_store = PersistentTicketStore {
+            store,
+            snapshot_path,
+            log_path,
+            log_writer,
+            changes_since_snapshot,
+        };
+        if persistent_store.changes_since_snapshot >= SNAPSHOT_INTERVAL {
+            persistent_store.flush_snapshot()?;
+        }
+        Ok(persistent_store)
+    }
+
+    /// Unlike `TicketStore::save`, this allocates the id and builds the ticket without
+    /// installing it into `self.store` yet - the ticket only becomes visible through
+    /// `get`/`list` once it's durably appended to the WAL. That way a failed `append` never
+    /// leaves an undurable ticket readable in memory; the worst it leaks is a reserved, empty
+    /// slot.
+    pub fn save(&mut self, draft: TicketDraft) -> Result<TicketId, StoreError> {
+        let id = self.store.allocate_id();
+        let timestamp = Utc::now();
+        let ticket = Ticket {
+            id,
+            title: draft.title,
+            description: draft.description,
+            status: Status::ToDo,
+            created_at: timestamp.clone(),
+            updated_at: timestamp,
+        };
+        self.append(ChangeRecord::Save(ticket.clone()))?;
+        self.store.slots[id.index as usize].ticket = Some(ticket.clone());
+        self.store.record_audit_entry(ChangeRecord::Save(ticket));
+        self.snapshot_if_due()?;
+
+        #[cfg(feature = "contracts")]
+        self.store.check_invariants();
+
+        Ok(id)
+    }
+
+    /// Computes the patched ticket and appends it to the WAL before mutating `self.store`, for
+    /// the same reason `save` does: a ticket's on-disk state must never lag what a caller can
+    /// already read back in memory.
+    pub fn update(&mut self, id: &TicketId, patch: TicketPatch) -> Result<&Ticket, StoreError> {
+        let not_found = StoreError::TicketNotFound(*id);
+        let slot = self.store.slots.get(id.index as usize).ok_or(not_found)?;
+        if slot.generation != id.generation {
+            return Err(StoreError::TicketNotFound(*id));
+        }
+        let mut ticket = slot.ticket.clone().ok_or(StoreError::TicketNotFound(*id))?;
+        if let Some(title) = patch.title {
+            ticket.title = title;
+        }
+        if let Some(description) = patch.description {
+            ticket.description = description;
+        }
+        if let Some(status) = patch.status {
+            ticket.status = status;
+        }
+        ticket.updated_at = Utc::now();
+
+        self.append(ChangeRecord::Update(ticket.clone()))?;
+        self.store.slots[id.index as usize].ticket = Some(ticket.clone());
+        self.store.record_audit_entry(ChangeRecord::Update(ticket));
+        self.snapshot_if_due()?;
+
+        #[cfg(feature = "contracts")]
+        self.store.check_invariants();
+
+        Ok(self.store.get(id).expect("we just updated this ticket"))
+    }
+
+    /// Confirms the ticket exists and appends the delete to the WAL before removing it from
+    /// `self.store`, so a crash between the two always leaves the ticket either fully present
+    /// or fully gone - never deleted in memory but still readable after a reopen.
+    pub fn delete(&mut self, id: &TicketId) -> Result<DeletedTicket, StoreError> {
+        let not_found = StoreError::TicketNotFound(*id);
+        let slot = self.store.slots.get(id.index as usize).ok_or(not_found)?;
+        if slot.generation != id.generation || slot.ticket.is_none() {
+            return Err(StoreError::TicketNotFound(*id));
+        }
+
+        self.append(ChangeRecord::Delete(*id))?;
+        let slot = &mut self.store.slots[id.index as usize];
+        let ticket = slot
+            .ticket
+            .take()
+            .expect("we just confirmed this slot holds a ticket");
+        slot.generation += 1;
+        self.store.free_list.push(id.index);
+        self.store.record_audit_entry(ChangeRecord::Delete(*id));
+        self.snapshot_if_due()?;
+
+        #[cfg(feature = "contracts")]
+        self.store.check_invariants();
+
+        Ok(DeletedTicket {
+            ticket,
+            deleted_at: Utc::now(),
+        })
+    }
+
+    pub fn get(&self, id: &TicketId) -> Option<&Ticket> {
+        self.store.get(id)
+    }
+
+    pub fn list(&self) -> Vec<&Ticket> {
+        self.store.list()
+    }
+
+    /// See the struct-level docs: the chain this attests to spans the store's entire history,
+    /// snapshot and replayed WAL records alike, not just mutations made since the last `open`.
+    pub fn audit_root(&self) -> [u8; 32] {
+        self.store.audit_root()
+    }
+
+    /// See the struct-level docs: this verifies the store's entire history, snapshot and
+    /// replayed WAL records alike, not just mutations made since the last `open`.
+    pub fn verify_chain(&self) -> Result<(), usize> {
+        self.store.verify_chain()
+    }
+
+    fn append(&mut self, record: ChangeRecord) -> Result<(), StoreError> {
+        let line = serde_json::to_string(&record).map_err(to_io_error)?;
+        writeln!(self.log_writer, "{}", line)?;
+        self.log_writer.flush()?;
+        self.changes_since_snapshot += 1;
+        Ok(())
+    }
+
+    /// Called once a freshly appended record has also been applied to `self.store`, so the
+    /// snapshot it may write reflects every record the log is about to be truncated of.
+    fn snapshot_if_due(&mut self) -> Result<(), StoreError> {
+        if self.changes_since_snapshot >= SNAPSHOT_INTERVAL {
+            self.flush_snapshot()?;
+        }
+        Ok(())
+    }
+
+    /// Writes out a full snapshot of the current state and truncates the log, since every
+    /// record in it is now reflected in the snapshot.
+    fn flush_snapshot(&mut self) -> Result<(), StoreError> {
+        let snapshot = Snapshot {
+            slots: self.store.slots.clone(),
+            free_list: self.store.free_list.clone(),
+            audit_log: self.store.audit_log.clone(),
+        };
+        let file = File::create(&self.snapshot_path)?;
+        serde_json::to_writer(BufWriter::new(file), &snapshot).map_err(to_io_error)?;
+
+        self.log_writer = BufWriter::new(
+            OpenOptions::new()
+                .write(true)
+                .create(true)
+                .truncate(true)
+                .open(&self.log_path)?,
+        );
+        self.changes_since_snapshot = 0;
+        Ok(())
+    }
+}
+
+/// Replays a previously logged `ChangeRecord` against an in-memory store. Used by `open` when
+/// recovering from disk.
+///
+/// Besides folding the record into `slots`/`free_list`, this also feeds it through
+/// `record_audit_entry` - replaying a WAL record is still a mutation, so the audit chain must
+/// pick back up from wherever the loaded snapshot's `audit_log` left off.
+fn apply(store: &mut TicketStore, record: ChangeRecord) {
+    match record.clone() {
+        ChangeRecord::Save(ticket) | ChangeRecord::Update(ticket) => {
+            let index = ticket.id.index as usize;
+            if index >= store.slots.len() {
+                store.slots.resize_with(index + 1, || Slot {
+                    generation: 0,
+                    ticket: None,
+                });
+            }
+            store.slots[index] = Slot {
+                generation: ticket.id.generation,
+                ticket: Some(ticket),
+            };
+            // The slot might still be sitting on the free list from an earlier `Delete` in
+            // this same log if a later `Save` reused its index - now that it holds a ticket
+            // again, it must come off, or the next `allocate_id` would hand its index out a
+            // second time and alias two live tickets onto the same slot.
+            store.free_list.retain(|&free_index| free_index != index as u32);
+        }
+        ChangeRecord::Delete(id) => {
+            if let Some(slot) = store.slots.get_mut(id.index as usize) {
+                slot.ticket = None;
+                slot.generation = id.generation + 1;
+                store.free_list.push(id.index);
+            }
+        }
+    }
+    store.record_audit_entry(record);
+}
+
+fn to_io_error(err: serde_json::Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
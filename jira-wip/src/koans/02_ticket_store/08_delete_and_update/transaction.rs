@@ -0,0 +1,162 @@
+use super::audit::ChangeRecord;
+use super::{Slot, Status, StoreError, Ticket, TicketDraft, TicketId, TicketPatch, TicketStore};
+use chrono::Utc;
+use std::collections::BTreeMap;
+
+/// A staged mutation, buffered by a `Transaction` until it's committed.
+enum Change {
+    Put(Ticket),
+    Delete,
+}
+
+/// A handle returned by `TicketStore::begin` that lets a caller batch up mutations and apply
+/// them atomically.
+///
+/// Every `save`/`update`/`delete` call goes through `buffer` instead of touching the
+/// underlying store's slots directly; `get`/`list` overlay `buffer` on top of the store's
+/// committed state, so reads through the transaction always see its own staged changes.
+///
+/// For simplicity, `save` within a transaction always allocates a brand new slot rather than
+/// reusing one freed earlier in the same transaction - the free list is only consulted by
+/// `TicketStore::save` once the transaction has been committed.
+pub struct Transaction<'a> {
+    store: &'a mut TicketStore,
+    buffer: BTreeMap<TicketId, Change>,
+    next_index: u32,
+}
+
+impl<'a> Transaction<'a> {
+    /// Builds a transaction rooted at `store`'s current state. Only `TicketStore::begin` should
+    /// call this - it's what keeps `next_index` consistent with the arena it's staged against.
+    pub(super) fn new(store: &'a mut TicketStore) -> Self {
+        let next_index = store.slots.len() as u32;
+        Transaction {
+            store,
+            buffer: BTreeMap::new(),
+            next_index,
+        }
+    }
+
+    pub fn save(&mut self, draft: TicketDraft) -> TicketId {
+        let id = self.generate_id();
+        let timestamp = Utc::now();
+        let ticket = Ticket {
+            id,
+            title: draft.title,
+            description: draft.description,
+            status: Status::ToDo,
+            created_at: timestamp.clone(),
+            updated_at: timestamp,
+        };
+        self.buffer.insert(id, Change::Put(ticket));
+        id
+    }
+
+    pub fn get(&self, id: &TicketId) -> Option<&Ticket> {
+        match self.buffer.get(id) {
+            Some(Change::Put(ticket)) => Some(ticket),
+            Some(Change::Delete) => None,
+            None => self.store.get(id),
+        }
+    }
+
+    pub fn list(&self) -> Vec<&Ticket> {
+        let mut tickets: BTreeMap<TicketId, &Ticket> = self
+            .store
+            .slots
+            .iter()
+            .enumerate()
+            .filter_map(|(index, slot)| {
+                let ticket = slot.ticket.as_ref()?;
+                let id = TicketId {
+                    index: index as u32,
+                    generation: slot.generation,
+                };
+                (!self.buffer.contains_key(&id)).then_some((id, ticket))
+            })
+            .collect();
+        for (id, change) in &self.buffer {
+            if let Change::Put(ticket) = change {
+                tickets.insert(*id, ticket);
+            }
+        }
+        tickets.into_values().collect()
+    }
+
+    pub fn update(&mut self, id: &TicketId, patch: TicketPatch) -> Result<&Ticket, StoreError> {
+        let mut ticket = self
+            .get(id)
+            .ok_or(StoreError::TicketNotFound(*id))?
+            .clone();
+        if let Some(title) = patch.title {
+            ticket.title = title;
+        }
+        if let Some(description) = patch.description {
+            ticket.description = description;
+        }
+        if let Some(status) = patch.status {
+            ticket.status = status;
+        }
+        ticket.updated_at = Utc::now();
+        self.buffer.insert(*id, Change::Put(ticket));
+        Ok(self.get(id).expect("we just staged this ticket"))
+    }
+
+    pub fn delete(&mut self, id: &TicketId) -> Result<(), StoreError> {
+        self.get(id).ok_or(StoreError::TicketNotFound(*id))?;
+        self.buffer.insert(*id, Change::Delete);
+        Ok(())
+    }
+
+    /// Folds every staged change into the underlying store in one shot, recording each one in
+    /// the audit trail exactly as `TicketStore::save`/`update`/`delete` would - a mutation made
+    /// through a transaction is still a mutation, and `audit_root`/`verify_chain` are only
+    /// trustworthy if they cover every way a ticket can change.
+    pub fn commit(self) {
+        let Transaction { store, buffer, .. } = self;
+        for (id, change) in buffer {
+            let index = id.index as usize;
+            if index >= store.slots.len() {
+                store.slots.resize_with(index + 1, || Slot {
+                    generation: 0,
+                    ticket: None,
+                });
+            }
+            match change {
+                Change::Put(ticket) => {
+                    let slot = &store.slots[index];
+                    let was_present =
+                        slot.generation == id.generation && slot.ticket.is_some();
+                    store.slots[index] = Slot {
+                        generation: id.generation,
+                        ticket: Some(ticket.clone()),
+                    };
+                    let record = if was_present {
+                        ChangeRecord::Update(ticket)
+                    } else {
+                        ChangeRecord::Save(ticket)
+                    };
+                    store.record_audit_entry(record);
+                }
+                Change::Delete => {
+                    let slot = &mut store.slots[index];
+                    if slot.generation == id.generation {
+                        slot.ticket = None;
+                        slot.generation += 1;
+                        store.free_list.push(id.index);
+                        store.record_audit_entry(ChangeRecord::Delete(id));
+                    }
+                }
+            }
+        }
+    }
+
+    fn generate_id(&mut self) -> TicketId {
+        let index = self.next_index;
+        self.next_index += 1;
+        TicketId {
+            index,
+            generation: 0,
+        }
+    }
+}